@@ -1,7 +1,9 @@
 use std::prelude::v1::*;
 
+use core::time::Duration;
 use std::io::{Error, ErrorKind, Write};
-use std::time::{Duration, Instant};
+#[cfg(any(feature = "std", feature = "tstd"))]
+use std::time::Instant;
 
 #[derive(Debug)]
 pub struct ExpandVec<T> {
@@ -49,6 +51,9 @@ impl<T: Clone> ExpandVec<T> {
 pub struct BufferVec {
     pub raw: Vec<u8>,
     size: usize,
+    // read cursor into the filled region `raw[..size]`, driving the
+    // `io::Read`/`io::BufRead`/`io::Seek` impls; independent of `size`.
+    pos: usize,
 }
 
 impl From<Vec<BufferVec>> for BufferVec {
@@ -68,6 +73,7 @@ impl BufferVec {
         Self {
             raw: vec![0_u8; size],
             size: 0,
+            pos: 0,
         }
     }
 
@@ -88,7 +94,11 @@ impl BufferVec {
         }
         let size = vec.len();
         vec.resize(cap, 0);
-        Self { raw: vec, size }
+        Self {
+            raw: vec,
+            size,
+            pos: 0,
+        }
     }
 
     pub fn to_vec(mut self) -> Vec<u8> {
@@ -141,10 +151,12 @@ impl BufferVec {
     pub fn rotate_left(&mut self, n: usize) {
         self.raw.rotate_left(n);
         self.size -= n;
+        self.pos = self.pos.saturating_sub(n);
     }
 
     pub fn clear(&mut self) {
         self.size = 0;
+        self.pos = 0;
     }
 
     /// try to read from `reader` until it's fulled.
@@ -167,6 +179,36 @@ impl BufferVec {
         Ok(())
     }
 
+    /// scatter-read from `reader` into a chain of buffers with a single
+    /// `read_vectored` syscall, advancing each buffer in order until the whole
+    /// returned count is consumed.
+    pub fn fill_vectored<R>(bufs: &mut [&mut BufferVec], reader: &mut R) -> Result<usize, Error>
+    where
+        R: std::io::Read,
+    {
+        use std::io::IoSliceMut;
+
+        let mut slices: Vec<IoSliceMut> = bufs
+            .iter_mut()
+            .map(|b| IoSliceMut::new(b.write()))
+            .collect();
+        let incoming_bytes = reader.read_vectored(&mut slices)?;
+        drop(slices);
+        if incoming_bytes == 0 {
+            return Err(Error::new(ErrorKind::UnexpectedEof, "unexpected EOF"));
+        }
+        let mut remaining = incoming_bytes;
+        for buf in bufs.iter_mut() {
+            if remaining == 0 {
+                break;
+            }
+            let n = remaining.min(buf.write().len());
+            buf.advance(n);
+            remaining -= n;
+        }
+        Ok(incoming_bytes)
+    }
+
     pub fn fill_with<R>(&mut self, reader: &mut R) -> Result<usize, Error>
     where
         R: std::io::Read,
@@ -191,6 +233,44 @@ impl BufferVec {
     }
 }
 
+impl std::io::Read for BufferVec {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, Error> {
+        let n = std::io::Read::read(&mut &self.raw[self.pos..self.size], buf)?;
+        self.pos += n;
+        Ok(n)
+    }
+}
+
+impl std::io::BufRead for BufferVec {
+    fn fill_buf(&mut self) -> Result<&[u8], Error> {
+        Ok(&self.raw[self.pos..self.size])
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.pos = (self.pos + amt).min(self.size);
+    }
+}
+
+impl std::io::Seek for BufferVec {
+    fn seek(&mut self, pos: std::io::SeekFrom) -> Result<u64, Error> {
+        use std::io::SeekFrom::*;
+        let (base, offset) = match pos {
+            Start(n) => (0_i64, n as i64),
+            End(n) => (self.size as i64, n),
+            Current(n) => (self.pos as i64, n),
+        };
+        let target = base + offset;
+        if target < 0 {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "invalid seek to a negative position",
+            ));
+        }
+        self.pos = (target as usize).min(self.size);
+        Ok(self.pos as u64)
+    }
+}
+
 #[derive(Debug)]
 pub enum IOError {
     WouldBlock, // the data is not accept
@@ -198,6 +278,16 @@ pub enum IOError {
     Other(Error),
 }
 
+impl From<IOError> for Error {
+    fn from(err: IOError) -> Error {
+        match err {
+            IOError::WouldBlock => Error::new(ErrorKind::WouldBlock, "would block"),
+            IOError::EOF { .. } => Error::new(ErrorKind::UnexpectedEof, "unexpected EOF"),
+            IOError::Other(err) => err,
+        }
+    }
+}
+
 impl From<Error> for IOError {
     fn from(err: Error) -> IOError {
         use ErrorKind::*;
@@ -211,30 +301,66 @@ impl From<Error> for IOError {
     }
 }
 
-#[derive(Debug)]
-pub struct WriteBuffer {
+/// A monotonic time source backing `WriteBuffer`'s idle tracking. The default
+/// `StdClock` wraps `std::time::Instant`, but SGX/embedded targets can supply
+/// their own source so the write-buffering subsystem stays `no_std`-friendly.
+pub trait Clock {
+    type Tick: Copy;
+
+    fn now(&self) -> Self::Tick;
+    fn since(&self, tick: Self::Tick) -> Duration;
+}
+
+#[cfg(any(feature = "std", feature = "tstd"))]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StdClock;
+
+#[cfg(any(feature = "std", feature = "tstd"))]
+impl Clock for StdClock {
+    type Tick = Instant;
+
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+
+    fn since(&self, tick: Instant) -> Duration {
+        tick.elapsed()
+    }
+}
+
+pub struct WriteBuffer<C: Clock = StdClock> {
     cap: usize,
     buf: Vec<BufferVec>,
-    idle_instant: Instant,
+    clock: C,
+    idle_tick: C::Tick,
 }
 
-impl WriteBuffer {
+#[cfg(any(feature = "std", feature = "tstd"))]
+impl WriteBuffer<StdClock> {
     pub fn new(cap: usize) -> Self {
+        Self::with_clock(cap, StdClock)
+    }
+}
+
+impl<C: Clock> WriteBuffer<C> {
+    pub fn with_clock(cap: usize, clock: C) -> Self {
+        let idle_tick = clock.now();
         Self {
             cap,
             buf: vec![BufferVec::new(cap)],
-            idle_instant: Instant::now(),
+            clock,
+            idle_tick,
         }
     }
 
     pub fn idle_duration(&self) -> Duration {
-        self.idle_instant.elapsed()
+        self.clock.since(self.idle_tick)
     }
 
     // Write to the writer or copy to the buffer, no WouldBlock
     pub fn must_write<W: Write>(&mut self, writer: &mut W, data: &[u8]) -> Result<(), IOError> {
         let written = match self.flush_buffer(writer) {
-            Ok(()) => match Self::raw_write(writer, data, &mut self.idle_instant) {
+            Ok(()) => match Self::raw_write(writer, data, &self.clock, &mut self.idle_tick) {
                 Ok(written) => written,
                 Err(IOError::WouldBlock) => 0,
                 Err(err) => return Err(err),
@@ -251,16 +377,92 @@ impl WriteBuffer {
         if self.buffered() > 0 {
             self.flush_buffer(writer)?;
         }
-        let written = Self::raw_write(writer, data, &mut self.idle_instant)?;
+        let written = Self::raw_write(writer, data, &self.clock, &mut self.idle_tick)?;
         self.copy_to_buffer(&data[written..]);
         return Ok(());
     }
 
     // flush all buffered data or WouldBlock or EOF
     pub fn flush_buffer<W: Write>(&mut self, writer: &mut W) -> Result<(), IOError> {
+        #[cfg(feature = "vectored")]
+        {
+            return self.flush_buffer_vectored(writer);
+        }
+        #[cfg(not(feature = "vectored"))]
+        {
+            self.flush_buffer_loop(writer)
+        }
+    }
+
+    // flush every queued chunk in a single `write_vectored` syscall, then
+    // distribute the written byte count back over the buffers exactly as
+    // `raw_write` does for a single buffer: leading buffers that were written
+    // in full are drained, and the first incompletely-written one is drained up
+    // to the consumed offset.
+    #[cfg(feature = "vectored")]
+    pub fn flush_buffer_vectored<W: Write>(&mut self, writer: &mut W) -> Result<(), IOError> {
+        use std::io::IoSlice;
+
+        let mut result = Ok(());
+        if self.buf.iter().any(|b| b.len() > 0) {
+            let slices: Vec<IoSlice> = self
+                .buf
+                .iter()
+                .filter(|b| b.len() > 0)
+                .map(|b| IoSlice::new(b.read()))
+                .collect();
+            match writer.write_vectored(&slices) {
+                Ok(mut written) => {
+                    for buf in &mut self.buf {
+                        let len = buf.len();
+                        if len == 0 {
+                            continue;
+                        }
+                        if written >= len {
+                            buf.rotate_left(len);
+                            written -= len;
+                        } else {
+                            buf.rotate_left(written);
+                            written = 0;
+                            break;
+                        }
+                    }
+                    self.idle_tick = self.clock.now();
+                    if self.buf.iter().any(|b| b.len() > 0) {
+                        result = Err(IOError::WouldBlock);
+                    }
+                }
+                Err(err)
+                    if matches!(err.kind(), ErrorKind::WouldBlock | ErrorKind::NotConnected) =>
+                {
+                    result = Err(IOError::WouldBlock);
+                }
+                Err(err) => result = Err(err.into()),
+            }
+        }
+
+        // compact the buffer
+        loop {
+            match self.buf.first() {
+                Some(first) => {
+                    if first.len() == 0 {
+                        self.buf.remove(0);
+                    } else {
+                        break;
+                    }
+                }
+                None => break,
+            }
+        }
+
+        result
+    }
+
+    #[cfg(not(feature = "vectored"))]
+    fn flush_buffer_loop<W: Write>(&mut self, writer: &mut W) -> Result<(), IOError> {
         let mut result = Ok(());
         for buf in &mut self.buf {
-            match Self::raw_write(writer, buf.read(), &mut self.idle_instant) {
+            match Self::raw_write(writer, buf.read(), &self.clock, &mut self.idle_tick) {
                 Ok(written) if written == buf.read().len() => {
                     buf.rotate_left(written);
                 }
@@ -315,7 +517,12 @@ impl WriteBuffer {
         self.buf.push(buf);
     }
 
-    fn raw_write<W>(w: &mut W, mut buf: &[u8], s: &mut Instant) -> Result<usize, IOError>
+    fn raw_write<W>(
+        w: &mut W,
+        mut buf: &[u8],
+        clock: &C,
+        tick: &mut C::Tick,
+    ) -> Result<usize, IOError>
     where
         W: Write,
     {
@@ -339,7 +546,95 @@ impl WriteBuffer {
                 Err(err) => return Err(err.into()),
             }
         }
-        *s = Instant::now();
+        *tick = clock.now();
         Ok(written)
     }
 }
+
+/// A `BufWriter`-style wrapper that owns both the underlying writer and a
+/// [`WriteBuffer`], so it can be dropped into any API expecting `io::Write`
+/// while transparently providing non-blocking buffering: on `write`, data the
+/// writer refuses with `WouldBlock` is kept in the internal buffer and the call
+/// succeeds; `flush` returns an error of kind `WouldBlock` while buffered data
+/// remains unwritten.
+pub struct BufferedWriter<W: Write, C: Clock = StdClock> {
+    writer: W,
+    buffer: WriteBuffer<C>,
+}
+
+#[cfg(any(feature = "std", feature = "tstd"))]
+impl<W: Write> BufferedWriter<W, StdClock> {
+    pub fn new(writer: W, cap: usize) -> Self {
+        Self {
+            writer,
+            buffer: WriteBuffer::new(cap),
+        }
+    }
+}
+
+impl<W: Write, C: Clock> BufferedWriter<W, C> {
+    pub fn with_clock(writer: W, cap: usize, clock: C) -> Self {
+        Self {
+            writer,
+            buffer: WriteBuffer::with_clock(cap, clock),
+        }
+    }
+
+    pub fn buffered(&self) -> usize {
+        self.buffer.buffered()
+    }
+
+    pub fn idle_duration(&self) -> Duration {
+        self.buffer.idle_duration()
+    }
+
+    pub fn get_ref(&self) -> &W {
+        &self.writer
+    }
+
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.writer
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+impl<W: Write, C: Clock> Write for BufferedWriter<W, C> {
+    fn write(&mut self, buf: &[u8]) -> Result<usize, Error> {
+        self.buffer.must_write(&mut self.writer, buf)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> Result<(), Error> {
+        self.buffer.flush_buffer(&mut self.writer)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Seek, SeekFrom};
+
+    #[test]
+    fn seek_clamps_to_filled_region() {
+        let mut buf = BufferVec::from_slice(b"hello", 8);
+
+        // seeks past the end clamp to `size`
+        assert_eq!(buf.seek(SeekFrom::Start(100)).unwrap(), 5);
+        assert_eq!(buf.seek(SeekFrom::End(0)).unwrap(), 5);
+        assert_eq!(buf.seek(SeekFrom::Start(2)).unwrap(), 2);
+        assert_eq!(buf.seek(SeekFrom::Current(2)).unwrap(), 4);
+
+        // seeking before the start is an InvalidInput error, position untouched
+        let err = buf.seek(SeekFrom::Current(-10)).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidInput);
+        assert_eq!(buf.seek(SeekFrom::Current(0)).unwrap(), 4);
+
+        // End with a negative offset rewinds within the region
+        assert_eq!(buf.seek(SeekFrom::End(-2)).unwrap(), 3);
+        assert!(buf.seek(SeekFrom::End(-100)).is_err());
+    }
+}
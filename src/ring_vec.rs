@@ -3,7 +3,7 @@ use std::prelude::v1::*;
 pub struct RingVec<T> {
     inner: Vec<Option<T>>,
     start: usize,
-    end: usize,
+    len: usize,
 }
 
 impl<T: Clone + PartialEq> RingVec<T> {
@@ -12,30 +12,120 @@ impl<T: Clone + PartialEq> RingVec<T> {
         Self {
             inner,
             start: 0,
-            end: 0,
+            len: 0,
         }
     }
 
+    pub fn cap(&self) -> usize {
+        self.inner.len()
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
     pub fn contains(&self, item: &T) -> bool {
         let item = Some(item);
         self.inner.iter().any(|n| n.as_ref() == item)
     }
 
     pub fn push(&mut self, item: T) {
-        let len = self.inner.len();
-        self.inner[self.end % len] = Some(item);
-        self.end = (self.end + 1) % self.inner.len();
-        if self.start == self.end {
-            self.start += 1;
+        let cap = self.inner.len();
+        let slot = (self.start + self.len) % cap;
+        self.inner[slot] = Some(item);
+        if self.len < cap {
+            self.len += 1;
+        } else {
+            // full: overwrite the oldest element and advance the window.
+            self.start = (self.start + 1) % cap;
         }
     }
 
+    pub fn pop_front(&mut self) -> Option<T> {
+        if self.len == 0 {
+            return None;
+        }
+        let item = self.inner[self.start].take();
+        self.start = (self.start + 1) % self.inner.len();
+        self.len -= 1;
+        item
+    }
+
     pub fn get(&self, idx: usize) -> Option<&T> {
-        let len = self.end - self.start;
-        if idx >= len {
+        if idx >= self.len {
             return None;
         }
-        let item = &self.inner[(idx + len) % self.inner.len()];
-        item.as_ref()
+        let slot = (self.start + idx) % self.inner.len();
+        self.inner[slot].as_ref()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        let cap = self.inner.len();
+        let start = self.start;
+        (0..self.len).map(move |i| self.inner[(start + i) % cap].as_ref().unwrap())
     }
-}
\ No newline at end of file
+}
+
+impl std::io::Write for RingVec<u8> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        for &b in buf {
+            self.push(b);
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl std::io::Read for RingVec<u8> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let mut n = 0;
+        while n < buf.len() {
+            match self.pop_front() {
+                Some(b) => {
+                    buf[n] = b;
+                    n += 1;
+                }
+                None => break,
+            }
+        }
+        Ok(n)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn get_and_pop_front_wrap_around() {
+        let mut ring = RingVec::new(3);
+        ring.push(1);
+        ring.push(2);
+        ring.push(3);
+        assert_eq!(ring.len(), 3);
+        assert_eq!(ring.get(0), Some(&1));
+        assert_eq!(ring.get(2), Some(&3));
+        assert_eq!(ring.get(3), None);
+
+        // overwrite the oldest element once the window is full
+        ring.push(4);
+        assert_eq!(ring.len(), 3);
+        assert_eq!(ring.get(0), Some(&2));
+        assert_eq!(ring.get(2), Some(&4));
+        assert_eq!(ring.iter().cloned().collect::<Vec<_>>(), vec![2, 3, 4]);
+
+        assert_eq!(ring.pop_front(), Some(2));
+        assert_eq!(ring.pop_front(), Some(3));
+        assert_eq!(ring.get(0), Some(&4));
+        assert_eq!(ring.pop_front(), Some(4));
+        assert_eq!(ring.pop_front(), None);
+        assert!(ring.is_empty());
+    }
+}